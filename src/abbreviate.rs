@@ -0,0 +1,144 @@
+//! Head-and-tail abbreviation of captured output for failure diagnostics.
+//!
+//! A test that dumps megabytes of stdout/stderr shouldn't have every byte
+//! printed back at the user when it fails. [`Abbreviator`] is an
+//! [`io::Write`] sink that keeps only the first and last handful of bytes
+//! once the total crosses [`Config::abbreviate_output_threshold_bytes`],
+//! replacing the middle with a `<<<N bytes omitted>>>` marker -- bounding
+//! memory use to roughly the threshold plus the tail window, rather than
+//! the full output.
+//!
+//! This is a *display-only* transform: a [`Matcher`] must always run
+//! against the real, un-abbreviated captured output, never against an
+//! [`Abbreviator`]'s result, or abbreviating a stream could turn a passing
+//! assertion into a spurious failure. Call [`Abbreviator::finish`] only when
+//! formatting a failure for a human to read.
+//!
+//! [`Config::abbreviate_output_threshold_bytes`]: crate::config::Config::abbreviate_output_threshold_bytes
+//! [`Matcher`]: crate::matcher::Matcher
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// Collects written bytes, abbreviating them to a head and a tail once the
+/// total exceeds `threshold`.
+pub struct Abbreviator {
+    threshold: usize,
+    head_limit: usize,
+    tail_limit: usize,
+    /// Holds up to `max(threshold, head_limit)` bytes: at least `threshold`
+    /// bytes so a stream that turns out to be under the threshold can still
+    /// be returned in full, and at least `head_limit` bytes so the abbreviated
+    /// head is never shorter than configured, even when `head_limit` exceeds
+    /// `threshold`.
+    head: Vec<u8>,
+    /// A rolling window of the most recent `tail_limit` bytes seen.
+    tail: VecDeque<u8>,
+    total_len: usize,
+}
+
+impl Abbreviator {
+    pub fn new(threshold: usize, head_and_tail_limit: usize) -> Self {
+        Abbreviator {
+            threshold,
+            head_limit: head_and_tail_limit,
+            tail_limit: head_and_tail_limit,
+            head: Vec::new(),
+            tail: VecDeque::new(),
+            total_len: 0,
+        }
+    }
+
+    /// Consumes the abbreviator, returning its (possibly-abbreviated) bytes.
+    pub fn finish(self) -> Vec<u8> {
+        if self.total_len <= self.threshold {
+            return self.head;
+        }
+
+        let mut result = self.head;
+        result.truncate(self.head_limit.min(result.len()));
+
+        // On a short stream the tail window can reach back into the kept
+        // head; drop those bytes from the tail so none appear twice.
+        let mut tail = self.tail;
+        let overlap = (result.len() + tail.len()).saturating_sub(self.total_len);
+        tail.drain(..overlap);
+
+        let omitted = self.total_len - result.len() - tail.len();
+        result.extend_from_slice(format!("\n<<<{} bytes omitted>>>\n", omitted).as_bytes());
+        result.extend(tail);
+        result
+    }
+}
+
+impl Write for Abbreviator {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.total_len += buf.len();
+
+        let head_cap = self.threshold.max(self.head_limit);
+        if self.head.len() < head_cap {
+            let take = (head_cap - self.head.len()).min(buf.len());
+            self.head.extend_from_slice(&buf[..take]);
+        }
+
+        for &byte in buf {
+            if self.tail.len() == self.tail_limit {
+                self.tail.pop_front();
+            }
+            self.tail.push_back(byte);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn output_under_the_threshold_is_returned_unabbreviated() {
+        let mut abbreviator = Abbreviator::new(100, 10);
+        abbreviator.write_all(b"well under the threshold").unwrap();
+
+        assert_eq!(b"well under the threshold".to_vec(), abbreviator.finish());
+    }
+
+    #[test]
+    fn output_over_the_threshold_keeps_only_head_and_tail() {
+        let mut abbreviator = Abbreviator::new(10, 4);
+        abbreviator.write_all(b"0123456789abcdefghij").unwrap(); // 20 bytes
+
+        let result = String::from_utf8(abbreviator.finish()).unwrap();
+        assert!(result.starts_with("0123"), "should keep the first 4 bytes: {}", result);
+        assert!(result.ends_with("ghij"), "should keep the last 4 bytes: {}", result);
+        assert!(result.contains("bytes omitted"), "should mark what was elided: {}", result);
+    }
+
+    #[test]
+    fn head_and_tail_are_symmetric_even_when_head_limit_exceeds_threshold() {
+        let mut abbreviator = Abbreviator::new(5, 10);
+        abbreviator.write_all(b"0123456789abcdefghij").unwrap(); // 20 bytes
+
+        let result = String::from_utf8(abbreviator.finish()).unwrap();
+        assert!(result.starts_with("0123456789"), "should keep all 10 head bytes: {}", result);
+        assert!(result.ends_with("abcdefghij"), "should keep all 10 tail bytes: {}", result);
+    }
+
+    #[test]
+    fn writes_can_be_split_across_multiple_calls() {
+        let mut one_shot = Abbreviator::new(10, 4);
+        one_shot.write_all(b"0123456789abcdefghij").unwrap();
+
+        let mut split = Abbreviator::new(10, 4);
+        for chunk in b"0123456789abcdefghij".chunks(3) {
+            split.write_all(chunk).unwrap();
+        }
+
+        assert_eq!(one_shot.finish(), split.finish());
+    }
+}
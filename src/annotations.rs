@@ -0,0 +1,274 @@
+//! Inline expected-diagnostic annotations, in the style of `compiletest-rs`.
+//!
+//! Rather than matching a test's diagnostics against a flat output stream,
+//! a test file can attach its expectations to individual source lines using
+//! trailing comment markers:
+//!
+//! * `//~ KIND message` expects `KIND: message` on the line it appears on.
+//! * `//~^ KIND message` expects it one line above, `//~^^` two lines above,
+//!   and so on -- one `^` per line moved up.
+//! * `//~| KIND message` expects it on the same line as the annotation
+//!   immediately before it; useful for attaching several diagnostics to one
+//!   line without repeating `^^^`.
+//!
+//! `KIND` is one of `ERROR`, `WARNING` (`WARN` is accepted as an alias),
+//! `NOTE`, `HELP` or `SUGGESTION`. The comment marker itself defaults to
+//! `//` but is configurable via [`Config::annotation_comment_prefix`] so
+//! non-`//` languages can use this subsystem too.
+//!
+//! An annotation may be scoped to one [`revisions`](crate::revisions) name
+//! with a `[name]` qualifier right after the comment prefix, e.g.
+//! `//[a]~ ERROR ...`; it is then only expected while that revision is
+//! active. Unscoped annotations apply to every revision.
+//!
+//! [`Config::annotation_comment_prefix`]: crate::config::Config::annotation_comment_prefix
+
+use std::fmt;
+
+/// The kind of diagnostic an annotation expects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    Error,
+    Warning,
+    Note,
+    Help,
+    Suggestion,
+}
+
+impl ErrorKind {
+    /// Parses a `KIND` token, accepting `WARN` as an alias for `WARNING`.
+    pub fn parse(s: &str) -> Option<ErrorKind> {
+        match s {
+            "ERROR" => Some(ErrorKind::Error),
+            "WARNING" | "WARN" => Some(ErrorKind::Warning),
+            "NOTE" => Some(ErrorKind::Note),
+            "HELP" => Some(ErrorKind::Help),
+            "SUGGESTION" => Some(ErrorKind::Suggestion),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            ErrorKind::Error => "ERROR",
+            ErrorKind::Warning => "WARNING",
+            ErrorKind::Note => "NOTE",
+            ErrorKind::Help => "HELP",
+            ErrorKind::Suggestion => "SUGGESTION",
+        };
+        s.fmt(fmt)
+    }
+}
+
+/// A single expected (or actual) diagnostic, attached to a source line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// The 1-indexed source line the diagnostic is expected on.
+    pub line_num: usize,
+    pub kind: ErrorKind,
+    /// The expected message. Matched as a substring against the actual
+    /// diagnostic's message, not exact equality.
+    pub msg: String,
+}
+
+/// The result of matching expected annotations against a tool's actual
+/// diagnostics: whatever didn't line up on either side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnnotationReport {
+    /// Annotations that no actual diagnostic satisfied.
+    pub unmatched_expected: Vec<Error>,
+    /// Actual diagnostics that no annotation expected.
+    pub unexpected_actual: Vec<Error>,
+}
+
+impl AnnotationReport {
+    /// Whether every annotation was satisfied and no extra diagnostics were emitted.
+    pub fn is_success(&self) -> bool {
+        self.unmatched_expected.is_empty() && self.unexpected_actual.is_empty()
+    }
+}
+
+/// Finds a `prefix~` or `prefix[name]~` marker in `line`, returning the byte
+/// offset just past it together with the revision name, if any.
+fn find_marker<'a>(line: &'a str, prefix: &str) -> Option<(usize, Option<&'a str>)> {
+    let mut search_from = 0;
+    while let Some(rel) = line[search_from..].find(prefix) {
+        let after_prefix = search_from + rel + prefix.len();
+        let tail = &line[after_prefix..];
+
+        if let Some(rest) = tail.strip_prefix('~') {
+            return Some((line.len() - rest.len(), None));
+        }
+
+        if let Some(inside) = tail.strip_prefix('[') {
+            if let Some(close) = inside.find(']') {
+                let name = &inside[..close];
+                if let Some(rest) = inside[close + 1..].strip_prefix('~') {
+                    return Some((line.len() - rest.len(), Some(name)));
+                }
+            }
+        }
+
+        search_from = after_prefix;
+    }
+    None
+}
+
+/// Parses every `//~`-style annotation out of `source`, using `prefix`
+/// (e.g. `"//"`) as the comment marker, keeping only those unscoped or
+/// scoped to `active_revision`.
+///
+/// Panics on a malformed annotation (an unknown `KIND`, a `//~|` with no
+/// preceding annotation, or an annotation mixing `^` and `|`) since these
+/// indicate a broken test file rather than a runtime condition.
+pub fn parse_expected_errors(prefix: &str, source: &str, active_revision: Option<&str>) -> Vec<Error> {
+    let mut errors = Vec::new();
+    let mut last_line: Option<usize> = None;
+
+    for (idx, line) in source.lines().enumerate() {
+        let line_num = idx + 1;
+
+        let (marker_end, revision) = match find_marker(line, prefix) {
+            Some(found) => found,
+            None => continue,
+        };
+
+        if let Some(name) = revision {
+            if Some(name) != active_revision {
+                continue;
+            }
+        }
+
+        let rest = &line[marker_end..];
+        let mut chars = rest.chars().peekable();
+
+        let mut carets = 0usize;
+        let mut follow = false;
+        loop {
+            match chars.peek() {
+                Some('^') => { carets += 1; chars.next(); },
+                Some('|') => { follow = true; chars.next(); break; },
+                _ => break,
+            }
+        }
+
+        if carets > 0 && follow {
+            panic!("{}: annotation mixes `^` and `|`, which is ambiguous", line_num);
+        }
+
+        let target_line = if follow {
+            last_line.unwrap_or_else(|| {
+                panic!("{}: `//~|` has no preceding annotation to follow", line_num)
+            })
+        } else {
+            line_num.checked_sub(carets)
+                .unwrap_or_else(|| panic!("{}: `^` moves the annotation above the start of the file", line_num))
+        };
+
+        let rest: String = chars.collect();
+        let rest = rest.trim_start();
+        let mut parts = rest.splitn(2, char::is_whitespace);
+        let kind_str = parts.next().unwrap_or("");
+        let msg = parts.next().unwrap_or("").trim().to_owned();
+
+        let kind = ErrorKind::parse(kind_str)
+            .unwrap_or_else(|| panic!("{}: unknown diagnostic kind '{}'", line_num, kind_str));
+
+        errors.push(Error { line_num: target_line, kind, msg });
+        last_line = Some(target_line);
+    }
+
+    errors
+}
+
+/// Matches `expected` annotations against a tool's `actual` diagnostics by
+/// `(line_num, kind)`, treating `msg` as a substring match, and reports
+/// whatever was left unmatched on either side.
+///
+/// Each actual diagnostic is consumed by at most one expected annotation, so
+/// duplicate expectations require duplicate actual diagnostics.
+pub fn match_against_actual(expected: &[Error], actual: &[Error]) -> AnnotationReport {
+    let mut remaining_actual: Vec<&Error> = actual.iter().collect();
+    let mut unmatched_expected = Vec::new();
+
+    for exp in expected {
+        let pos = remaining_actual.iter().position(|act| {
+            act.line_num == exp.line_num && act.kind == exp.kind && act.msg.contains(&exp.msg)
+        });
+
+        match pos {
+            Some(idx) => { remaining_actual.remove(idx); },
+            None => unmatched_expected.push(exp.clone()),
+        }
+    }
+
+    AnnotationReport {
+        unmatched_expected,
+        unexpected_actual: remaining_actual.into_iter().cloned().collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_a_same_line_annotation() {
+        let errors = parse_expected_errors("//", "let x = 1 //~ ERROR mismatched types", None);
+        assert_eq!(vec![Error { line_num: 1, kind: ErrorKind::Error, msg: "mismatched types".to_owned() }],
+                   errors);
+    }
+
+    #[test]
+    fn caret_moves_the_expectation_up() {
+        let source = "let x: u8 = \"\";\n//~^ ERROR mismatched types\n";
+        let errors = parse_expected_errors("//", source, None);
+        assert_eq!(1, errors[0].line_num, "single `^` should point at the previous line");
+    }
+
+    #[test]
+    fn pipe_inherits_the_previous_annotations_line() {
+        let source = "let x: u8 = \"\";\n//~^ ERROR mismatched types\n//~| NOTE expected due to this\n";
+        let errors = parse_expected_errors("//", source, None);
+        assert_eq!(errors[0].line_num, errors[1].line_num, "`//~|` should share the `^` target line");
+    }
+
+    #[test]
+    #[should_panic(expected = "mixes `^` and `|`")]
+    fn mixing_caret_and_pipe_is_rejected() {
+        parse_expected_errors("//", "//~^| ERROR nope", None);
+    }
+
+    #[test]
+    fn revision_scoped_annotations_only_apply_to_their_revision() {
+        let source = "//[a]~ ERROR only under a\n//[b]~ ERROR only under b\n//~ NOTE always\n";
+
+        let under_a = parse_expected_errors("//", source, Some("a"));
+        assert_eq!(vec![ErrorKind::Error, ErrorKind::Note],
+                   under_a.iter().map(|e| e.kind).collect::<Vec<_>>());
+
+        let under_neither = parse_expected_errors("//", source, None);
+        assert_eq!(vec![ErrorKind::Note],
+                   under_neither.iter().map(|e| e.kind).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unmatched_and_unexpected_are_reported_separately() {
+        let expected = vec![
+            Error { line_num: 3, kind: ErrorKind::Error, msg: "mismatched types".to_owned() },
+            Error { line_num: 5, kind: ErrorKind::Note, msg: "nowhere".to_owned() },
+        ];
+        let actual = vec![
+            Error { line_num: 3, kind: ErrorKind::Error, msg: "mismatched types: expected u8".to_owned() },
+            Error { line_num: 9, kind: ErrorKind::Warning, msg: "unused variable".to_owned() },
+        ];
+
+        let report = match_against_actual(&expected, &actual);
+        assert_eq!(1, report.unmatched_expected.len());
+        assert_eq!(5, report.unmatched_expected[0].line_num);
+        assert_eq!(1, report.unexpected_actual.len());
+        assert_eq!(9, report.unexpected_actual[0].line_num);
+    }
+}
@@ -10,7 +10,11 @@ use std::collections::HashMap;
 use std::fmt;
 use tempfile::NamedTempFile;
 
+use crate::which;
+
 const DEFAULT_MAX_OUTPUT_CONTEXT_LINE_COUNT: usize = 10;
+const DEFAULT_ABBREVIATE_OUTPUT_THRESHOLD_BYTES: usize = 1024 * 1024;
+const DEFAULT_ABBREVIATE_OUTPUT_HEAD_AND_TAIL_BYTES: usize = 8 * 1024;
 
 /// The configuration of the test runner.
 #[derive(Clone, Debug)]
@@ -52,6 +56,40 @@ pub struct Config
     pub shell: String,
     /// List of environment variables to be used on test invocation.
     pub env_variables: HashMap<String, String>,
+    /// The comment marker that precedes inline expected-diagnostic
+    /// annotations (`//~ KIND message`), as parsed by the [`annotations`]
+    /// module. Defaults to `"//"`; override it for languages that don't use
+    /// C-style line comments.
+    ///
+    /// [`annotations`]: crate::annotations
+    pub annotation_comment_prefix: String,
+    /// The name of the revision currently being run, for a test using
+    /// `// revisions: ...` (see the [`revisions`] module). `None` outside of
+    /// a revisioned test, or while running a test with no revisions.
+    ///
+    /// [`revisions`]: crate::revisions
+    pub active_revision: Option<String>,
+    /// Instead of failing when a test's captured output doesn't match its
+    /// golden `.stdout`/`.stderr` file, rewrite the golden file with the
+    /// actual output. See the [`golden`] module.
+    ///
+    /// [`golden`]: crate::golden
+    pub bless: bool,
+    /// The number of test files to run concurrently. Defaults to the
+    /// available parallelism when unset. See [`pool::worker_count`].
+    ///
+    /// [`pool::worker_count`]: crate::pool::worker_count
+    pub jobs: Option<usize>,
+    /// If set, captured stdout/stderr exceeding this many bytes is
+    /// abbreviated to a head and a tail when displayed in a failure
+    /// diagnostic (never when matching -- see the [`abbreviate`] module).
+    ///
+    /// [`abbreviate`]: crate::abbreviate
+    pub abbreviate_output_threshold_bytes: Option<usize>,
+    /// How many bytes of head and of tail to retain when abbreviating
+    /// output for display. Only meaningful alongside
+    /// `abbreviate_output_threshold_bytes`.
+    pub abbreviate_output_head_and_tail_bytes: usize,
 }
 
 /// A function which can dynamically define newly used variables in a test.
@@ -130,6 +168,26 @@ impl Config
 
         variables.get(name).expect(&format!("no variable with the name '{}' exists", name))
     }
+
+    /// Resolves `name` to an absolute executable path, searching
+    /// `extra_executable_search_paths` before the inherited `$PATH` (and,
+    /// on Windows, honoring `PATHEXT`). See the [`which`] module.
+    ///
+    /// Exposed so a custom `variable_lookup` can resolve tool paths too.
+    ///
+    /// [`which`]: crate::which
+    pub fn resolve_executable(&self, name: &str) -> Option<PathBuf> {
+        which::resolve_executable(name, &self.extra_executable_search_paths)
+    }
+
+    /// Resolves the configured `shell`. See the [`which`] module for why
+    /// this is worth doing up front.
+    ///
+    /// [`which`]: crate::which
+    pub fn resolve_shell(&self) -> Result<PathBuf, String> {
+        self.resolve_executable(&self.shell)
+            .ok_or_else(|| format!("configured shell '{}' was not found in the search paths or on $PATH", self.shell))
+    }
 }
 
 impl Default for Config
@@ -158,6 +216,12 @@ impl Default for Config
             extra_executable_search_paths,
             shell: "bash".to_string(),
             env_variables: HashMap::default(),
+            annotation_comment_prefix: "//".to_string(),
+            active_revision: None,
+            bless: false,
+            jobs: None,
+            abbreviate_output_threshold_bytes: Some(DEFAULT_ABBREVIATE_OUTPUT_THRESHOLD_BYTES),
+            abbreviate_output_head_and_tail_bytes: DEFAULT_ABBREVIATE_OUTPUT_HEAD_AND_TAIL_BYTES,
         }
     }
 }
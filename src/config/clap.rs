@@ -0,0 +1,64 @@
+//! Optional [`clap`](https://docs.rs/clap)-derived command line arguments
+//! for a `lit`-based test binary. Enabled by the `clap` feature.
+
+use clap::Parser;
+
+use super::Config;
+
+/// Command line flags a `lit`-based test runner may want to expose as-is.
+///
+/// Parse these with [`Args::parse`] and apply them to a `Config` built up
+/// however the binary likes with [`Args::apply_to`].
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Instead of failing when captured output doesn't match a test's
+    /// golden `.stdout`/`.stderr` file, rewrite the golden file with the
+    /// actual output.
+    #[arg(long)]
+    pub bless: bool,
+    /// Number of test files to run concurrently. Defaults to the available
+    /// parallelism.
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    /// Abbreviate captured stdout/stderr over this many bytes to a head and
+    /// a tail when displaying a failure. Defaults to 1 MiB.
+    #[arg(long)]
+    pub abbreviate_output_threshold_bytes: Option<usize>,
+}
+
+impl Args {
+    /// Applies these parsed flags onto an existing `Config`. An `Option`
+    /// flag that wasn't passed leaves the corresponding `Config` field (and
+    /// thus its default) untouched, rather than clobbering it with `None`.
+    /// `--bless` is a plain boolean flag, so it can only turn blessing on;
+    /// not passing it leaves a pre-set `config.bless` as-is rather than
+    /// forcing it back to `false`.
+    pub fn apply_to(&self, config: &mut Config) {
+        config.bless = config.bless || self.bless;
+        config.jobs = self.jobs.or(config.jobs);
+        config.abbreviate_output_threshold_bytes =
+            self.abbreviate_output_threshold_bytes.or(config.abbreviate_output_threshold_bytes);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn absent_flags_leave_preset_config_fields_untouched() {
+        let args = Args { bless: false, jobs: None, abbreviate_output_threshold_bytes: None };
+
+        let mut config = Config::default();
+        config.bless = true;
+        config.jobs = Some(4);
+        config.abbreviate_output_threshold_bytes = Some(2048);
+
+        args.apply_to(&mut config);
+
+        assert!(config.bless, "a preset bless should survive apply_to when --bless wasn't passed");
+        assert_eq!(Some(4), config.jobs, "a preset jobs should survive apply_to when --jobs wasn't passed");
+        assert_eq!(Some(2048), config.abbreviate_output_threshold_bytes,
+                   "a preset threshold should survive apply_to when the flag wasn't passed");
+    }
+}
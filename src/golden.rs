@@ -0,0 +1,256 @@
+//! Golden ("snapshot") output testing: compare a test's captured stdout and
+//! stderr against sibling expected-output files, e.g. `foo.txt.stdout` and
+//! `foo.txt.stderr` next to `foo.txt`.
+//!
+//! Set [`Config::bless`] (wired up via the [`clap`](crate::config::clap)
+//! feature as `--bless`) to rewrite the expected files with the actual
+//! output instead of failing on a mismatch. Before comparing or blessing,
+//! run the captured output through [`canonicalize`] so volatile values
+//! bound during the test -- a `$$tempfile` path, for instance -- are
+//! replaced by their placeholder and never cause (or paper over) spurious
+//! diffs.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Which captured stream a golden file holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn extension(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+/// The outcome of checking (or blessing) one stream's golden file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GoldenResult {
+    /// The captured output matched the expected file byte-for-byte.
+    Matched,
+    /// The captured output did not match; `diff` is a human-readable diff
+    /// of expected vs. actual, suitable for printing as a failure.
+    Mismatch { diff: String },
+    /// `Config::bless` was set, so the expected file was rewritten instead
+    /// of compared.
+    Blessed,
+}
+
+/// The path of the golden file for `test_path`'s given `stream`, e.g.
+/// `foo.txt` -> `foo.txt.stdout`.
+pub fn expected_output_path(test_path: &Path, stream: Stream) -> PathBuf {
+    let mut name = test_path.as_os_str().to_owned();
+    name.push(".");
+    name.push(stream.extension());
+    PathBuf::from(name)
+}
+
+/// Replaces every occurrence of a bound variable's value in `text` with its
+/// canonical `$$name` placeholder.
+///
+/// This undoes what [`crate::matcher::Matcher::resolve`] does when
+/// substituting a variable into a pattern, so a volatile value -- most
+/// commonly a `@tempfile`-derived path, which is different on every run --
+/// compares (and blesses) identically across runs.
+///
+/// Bindings are substituted longest-value-first, so one value that happens
+/// to be a substring of another (plausible for tempfile-derived paths
+/// sharing a prefix) can't be replaced out from under the longer one
+/// depending on `HashMap`'s unspecified iteration order.
+pub fn canonicalize(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut bindings: Vec<(&String, &String)> = variables.iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    bindings.sort_by_key(|(_, b)| std::cmp::Reverse(b.len()));
+
+    let mut result = text.to_owned();
+    for (name, value) in bindings {
+        result = result.replace(value.as_str(), &format!("$${}", name));
+    }
+    result
+}
+
+/// Checks `actual` (already read from the test's captured `stream`) against
+/// its golden file, or rewrites the golden file when `config.bless` is set.
+///
+/// A blessed file is written under `config.save_artifacts_to_directory`
+/// when set, reusing the same destination other generated test artifacts
+/// land in; otherwise it overwrites the golden file next to the test.
+pub fn check_or_bless(
+    config: &Config,
+    test_path: &Path,
+    stream: Stream,
+    actual: &str,
+    variables: &HashMap<String, String>,
+) -> io::Result<GoldenResult> {
+    let actual = canonicalize(actual, variables);
+    let expected_path = expected_output_path(test_path, stream);
+
+    if config.bless {
+        let destination = match &config.save_artifacts_to_directory {
+            Some(dir) => dir.join(expected_path.file_name().expect("golden path has a file name")),
+            None => expected_path,
+        };
+        fs::write(&destination, &actual)?;
+        return Ok(GoldenResult::Blessed);
+    }
+
+    let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+    if expected == actual {
+        Ok(GoldenResult::Matched)
+    } else {
+        Ok(GoldenResult::Mismatch { diff: unified_diff(&expected, &actual) })
+    }
+}
+
+/// A minimal unified-style diff: an LCS-based line alignment prefixed with
+/// `- ` for expected-only lines, `+ ` for actual-only lines, and two spaces
+/// for unchanged context. Good enough for the (typically short) output a
+/// test captures; it doesn't chunk the result into `@@` hunks.
+fn unified_diff(expected: &str, actual: &str) -> String {
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let (n, m) = (expected_lines.len(), actual_lines.len());
+
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if expected_lines[i] == actual_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::from("--- expected\n+++ actual\n");
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if expected_lines[i] == actual_lines[j] {
+            diff.push_str("  ");
+            diff.push_str(expected_lines[i]);
+            diff.push('\n');
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push_str("- ");
+            diff.push_str(expected_lines[i]);
+            diff.push('\n');
+            i += 1;
+        } else {
+            diff.push_str("+ ");
+            diff.push_str(actual_lines[j]);
+            diff.push('\n');
+            j += 1;
+        }
+    }
+    for line in &expected_lines[i..] {
+        diff.push_str("- ");
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    for line in &actual_lines[j..] {
+        diff.push_str("+ ");
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expected_path_appends_the_stream_extension() {
+        assert_eq!(PathBuf::from("foo.txt.stdout"),
+                   expected_output_path(Path::new("foo.txt"), Stream::Stdout));
+        assert_eq!(PathBuf::from("foo.txt.stderr"),
+                   expected_output_path(Path::new("foo.txt"), Stream::Stderr));
+    }
+
+    #[test]
+    fn canonicalize_replaces_bound_values_with_their_placeholder() {
+        let mut variables = HashMap::new();
+        variables.insert("tempfile".to_owned(), "/tmp/abc123".to_owned());
+
+        let actual = canonicalize("wrote to /tmp/abc123 successfully", &variables);
+        assert_eq!("wrote to $$tempfile successfully", actual);
+    }
+
+    #[test]
+    fn canonicalize_prefers_the_longer_match_when_one_value_is_a_prefix_of_another() {
+        // Run several times with a freshly built map: each map gets its own
+        // hash seed, so an implementation that depended on HashMap
+        // iteration order would flip between the correct and incorrect
+        // result across iterations.
+        for _ in 0..20 {
+            let mut variables = HashMap::new();
+            variables.insert("tempfile".to_owned(), "/tmp/abc".to_owned());
+            variables.insert("other".to_owned(), "/tmp/abc123".to_owned());
+
+            let actual = canonicalize("value is /tmp/abc123 done", &variables);
+            assert_eq!("value is $$other done", actual,
+                       "the longer, more specific value should win regardless of iteration order");
+        }
+    }
+
+    #[test]
+    fn matching_output_does_not_need_the_golden_file_on_disk() {
+        let config = Config::default();
+        let result = check_or_bless(&config, Path::new("/nonexistent/foo.txt"), Stream::Stdout,
+                                     "", &HashMap::new()).unwrap();
+        assert_eq!(GoldenResult::Matched, result, "an absent golden file and empty output should match");
+    }
+
+    #[test]
+    fn mismatched_output_reports_a_diff_against_the_golden_file() {
+        let dir = tempfile::tempdir().expect("failed to create a temp dir");
+        let test_path = dir.path().join("foo.txt");
+        let golden_path = expected_output_path(&test_path, Stream::Stdout);
+        fs::write(&golden_path, "line one\nline two\n").expect("failed to write golden file");
+
+        let config = Config::default();
+        let result = check_or_bless(&config, &test_path, Stream::Stdout,
+                                     "line one\nline three\n", &HashMap::new()).unwrap();
+
+        match result {
+            GoldenResult::Mismatch { diff } => {
+                assert!(diff.contains("- line two"), "diff should show the removed line: {}", diff);
+                assert!(diff.contains("+ line three"), "diff should show the added line: {}", diff);
+            }
+            other => panic!("expected a Mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn blessing_rewrites_the_golden_file_with_canonicalized_output() {
+        let dir = tempfile::tempdir().expect("failed to create a temp dir");
+        let test_path = dir.path().join("foo.txt");
+        let golden_path = expected_output_path(&test_path, Stream::Stdout);
+        fs::write(&golden_path, "stale expected output\n").expect("failed to write golden file");
+
+        let mut config = Config::default();
+        config.bless = true;
+
+        let mut variables = HashMap::new();
+        variables.insert("tempfile".to_owned(), "/tmp/abc123".to_owned());
+
+        let result = check_or_bless(&config, &test_path, Stream::Stdout,
+                                     "wrote to /tmp/abc123 successfully", &variables).unwrap();
+
+        assert_eq!(GoldenResult::Blessed, result);
+        let on_disk = fs::read_to_string(&golden_path).expect("blessed file should exist");
+        assert_eq!("wrote to $$tempfile successfully", on_disk);
+    }
+}
@@ -0,0 +1,22 @@
+//! `lit` is a simple, extensible tool for running file-based tests, similar
+//! in spirit to LLVM's `lit`/`FileCheck`.
+//!
+//! A test is a plain text file containing `RUN:` lines (commands to
+//! execute) and `CHECK`-style assertions (matched against the commands'
+//! output using [`matcher::Matcher`]). See [`config::Config`] for how to
+//! configure and run a test suite.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod abbreviate;
+pub mod annotations;
+pub mod config;
+pub mod golden;
+pub mod matcher;
+pub mod pool;
+pub mod revisions;
+pub mod which;
+
+pub use config::Config;
+pub use matcher::Matcher;
@@ -1,6 +1,6 @@
 use regex::{self, Regex};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::{fmt, mem};
 
 lazy_static! {
@@ -38,6 +38,7 @@ impl Matcher {
             match (chars.next(), chars.peek().cloned()) {
                 // Variable.
                 (Some('$'), Some('$')) => {
+                    complete_text(&mut current_text, &mut components);
                     chars.next(); // Eat second '$'.
 
                     let name: String = chars.clone()
@@ -108,19 +109,60 @@ impl Matcher {
 
         Matcher { components: components }
     }
+    /// Compiles this matcher into a [`Regex`], substituting any
+    /// [`Component::Variable`] with the literal (regex-escaped) text
+    /// previously bound for it in `variables`.
+    ///
+    /// Precedence: a variable is resolved from whatever is already sitting
+    /// in `variables` when this call is made -- that may be a [`Config`]
+    /// constant, or a capture bound by [`Matcher::bind_captures`] after an
+    /// earlier line matched. A name cannot be both *used* (`$$name`) and
+    /// *defined* (`[[name:regex]]`) on the same line: the define has not
+    /// happened yet while this line is still being matched, so that
+    /// combination is rejected rather than silently resolved against a
+    /// stale or absent value.
+    ///
+    /// [`Config`]: crate::config::Config
     pub fn resolve(&self, variables: &HashMap<String, String>) -> Regex {
+        let defined_names: HashSet<&str> = self.components.iter()
+            .filter_map(|comp| match *comp {
+                Component::NamedRegex { ref name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+
         let regex_parts: Vec<_> = self.components.iter().map(|comp| match *comp {
             Component::Text(ref text) => regex::escape(text),
             Component::Variable(ref name) => {
+                assert!(!defined_names.contains(name.as_str()),
+                        "variable '{}' is both defined and used on the same line", name);
+
                 // FIXME: proper error handling.
                 let value = variables.get(name).expect("no variable with that name");
-                value.clone()
+                regex::escape(value)
             },
             Component::Regex(ref regex) => regex.clone(),
             Component::NamedRegex { ref name, ref regex } => format!("(?P<{}>{})", name, regex),
         }).collect();
         Regex::new(&regex_parts.join("")).expect("generated invalid line match regex")
     }
+
+    /// Copies the matched text of every named capture this matcher defines
+    /// back into `variables`, so that a later line's `$$name` can resolve
+    /// to the concrete text captured here.
+    ///
+    /// Call this once a line has matched, passing the [`regex::Captures`]
+    /// produced by matching the [`Regex`] returned from [`Matcher::resolve`]
+    /// against that line.
+    pub fn bind_captures(&self, captures: &regex::Captures, variables: &mut HashMap<String, String>) {
+        for component in &self.components {
+            if let Component::NamedRegex { ref name, .. } = *component {
+                if let Some(value) = captures.name(name) {
+                    variables.insert(name.clone(), value.as_str().to_owned());
+                }
+            }
+        }
+    }
 }
 
 impl fmt::Display for Matcher {
@@ -138,3 +180,42 @@ impl fmt::Display for Matcher {
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_keeps_text_around_a_variable_as_its_own_component() {
+        let matcher = Matcher::parse("a $$id b");
+        assert_eq!(vec![
+            Component::Text("a ".to_owned()),
+            Component::Variable("id".to_owned()),
+            Component::Text(" b".to_owned()),
+        ], matcher.components);
+    }
+
+    #[test]
+    fn captured_variable_is_reused_on_a_later_line() {
+        let define = Matcher::parse("id=[[id:\\d+]]");
+        let mut variables = HashMap::new();
+
+        let regex = define.resolve(&variables);
+        let captures = regex.captures("id=42").expect("first line should match");
+        define.bind_captures(&captures, &mut variables);
+
+        assert_eq!("42", variables.get("id").expect("id should have been bound"));
+
+        let reuse = Matcher::parse("seen id $$id, twice: $$id");
+        let regex = reuse.resolve(&variables);
+        assert!(regex.is_match("seen id 42, twice: 42"),
+                "later line should match the bound value, with surrounding and repeated text intact");
+        assert!(!regex.is_match("seen id 43, twice: 43"), "later line should not match a different value");
+    }
+
+    #[test]
+    #[should_panic(expected = "is both defined and used on the same line")]
+    fn defining_and_using_a_variable_on_the_same_line_is_rejected() {
+        let matcher = Matcher::parse("[[id:\\d+]] again $$id");
+        matcher.resolve(&HashMap::new());
+    }
+}
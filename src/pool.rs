@@ -0,0 +1,111 @@
+//! A small worker pool for running independent test files concurrently.
+//!
+//! The pool shares one [`Config`] across workers via `Arc` rather than
+//! cloning it per test, since `constants`, `variable_lookup` and the search
+//! paths are read-only for the duration of a run. Each worker gets its own
+//! `variables` map per test it claims, seeded fresh from `config.constants`,
+//! so `@tempfile` allocations and captures bound by
+//! [`Matcher::bind_captures`] in one test can never leak into another.
+//!
+//! [`Matcher::bind_captures`]: crate::matcher::Matcher::bind_captures
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::config::Config;
+
+/// The number of workers a pool should use: `config.jobs` if set, otherwise
+/// the available parallelism (falling back to 1 if that can't be
+/// determined).
+pub fn worker_count(config: &Config) -> usize {
+    config.jobs.unwrap_or_else(|| {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    })
+}
+
+/// Runs `run_one` once per entry in `tests`, across a pool of
+/// [`worker_count`] threads, and returns the results in the same order as
+/// `tests` regardless of which worker finished which test first.
+///
+/// `run_one` receives the shared `Config` and a `variables` map private to
+/// the test it was just handed -- pre-populated with `config.constants`.
+pub fn run_tests<T, F>(config: &Arc<Config>, tests: Vec<PathBuf>, run_one: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Config, &Path, &mut HashMap<String, String>) -> T + Send + Sync,
+{
+    let worker_count = worker_count(config).max(1).min(tests.len().max(1));
+    let next_index = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<T>>> = Mutex::new((0..tests.len()).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            let config = Arc::clone(config);
+            let tests = &tests;
+            let next_index = &next_index;
+            let results = &results;
+            let run_one = &run_one;
+
+            scope.spawn(move || loop {
+                let index = next_index.fetch_add(1, Ordering::SeqCst);
+                if index >= tests.len() {
+                    break;
+                }
+
+                // Fresh per test, not per worker: a worker runs many tests
+                // in sequence, and their variables must not leak into one
+                // another any more than two tests on different workers would.
+                let mut variables = config.constants.clone();
+                let result = run_one(&config, &tests[index], &mut variables);
+
+                results.lock().expect("results mutex poisoned")[index] = Some(result);
+            });
+        }
+    });
+
+    results.into_inner().expect("results mutex poisoned")
+        .into_iter()
+        .map(|r| r.expect("every test index should have been claimed by a worker"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn results_preserve_input_order_regardless_of_completion_order() {
+        let config = Arc::new(Config::default());
+        let tests: Vec<PathBuf> = (0..50).map(|i| PathBuf::from(format!("test-{}.txt", i))).collect();
+
+        let results = run_tests(&config, tests.clone(), |_config, path, _variables| {
+            // The last test yields before returning; whatever order the
+            // workers finish in, results must come back in input order.
+            if path.to_str().unwrap().ends_with("49.txt") {
+                thread::yield_now();
+            }
+            path.to_owned()
+        });
+
+        assert_eq!(tests, results);
+    }
+
+    #[test]
+    fn each_test_gets_a_variables_map_seeded_from_constants_only() {
+        let mut config = Config::default();
+        config.constants.insert("name".to_owned(), "bob".to_owned());
+        let config = Arc::new(config);
+
+        let tests: Vec<PathBuf> = vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")];
+        let results = run_tests(&config, tests, |_config, _path, variables| {
+            let seen_extra = variables.len() > 1;
+            variables.insert("leaked".to_owned(), "oops".to_owned());
+            seen_extra
+        });
+
+        assert_eq!(vec![false, false], results, "no test should see another test's bound variables");
+    }
+}
@@ -0,0 +1,75 @@
+//! Support for running a single test file under several named
+//! configurations ("revisions"), the way compiler test suites exercise one
+//! input across a handful of feature flags without duplicating the file.
+//!
+//! A test opts in with a directive naming its revisions:
+//!
+//! ```text
+//! // revisions: a b c
+//! ```
+//!
+//! Every other directive or [`annotations`](crate::annotations) entry
+//! applies to all revisions unless it is scoped to one with a `[name]`
+//! qualifier right after the comment prefix, e.g. `//[a]~ ERROR ...` or a
+//! `RUN:` line gated on a revision. The runner is expected to call
+//! [`parse_revisions`] once per test file and, if it returns `Some`, run the
+//! file once per name via [`Config::for_revision`], reporting each as its
+//! own pass/fail.
+
+use crate::config::Config;
+
+/// Parses the `// revisions: a b c` directive, if present, into its list of
+/// revision names. Returns `None` for a test file with no such directive,
+/// meaning it should be run exactly once, unscoped.
+pub fn parse_revisions(prefix: &str, source: &str) -> Option<Vec<String>> {
+    let directive = format!("{} revisions:", prefix);
+
+    for line in source.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix(&directive) {
+            let names: Vec<String> = rest.split_whitespace().map(str::to_owned).collect();
+            assert!(!names.is_empty(), "`revisions:` directive must name at least one revision");
+            return Some(names);
+        }
+    }
+
+    None
+}
+
+impl Config {
+    /// Derives the `Config` a single revision of a test should run under:
+    /// the revision's name becomes both the `rev` constant (so `@rev` /
+    /// `$$rev` resolve to it via [`Config::lookup_variable`]) and the `REV`
+    /// environment variable, so the invoked process can branch on it too.
+    pub fn for_revision(&self, revision: &str) -> Config {
+        let mut config = self.clone();
+        config.constants.insert("rev".to_owned(), revision.to_owned());
+        config.env_variables.insert("REV".to_owned(), revision.to_owned());
+        config.active_revision = Some(revision.to_owned());
+        config
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_a_revisions_directive() {
+        let revisions = parse_revisions("//", "// revisions: a b c\nRUN: echo hi\n");
+        assert_eq!(Some(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]), revisions);
+    }
+
+    #[test]
+    fn a_file_with_no_directive_has_no_revisions() {
+        assert_eq!(None, parse_revisions("//", "RUN: echo hi\n"));
+    }
+
+    #[test]
+    fn for_revision_injects_the_rev_constant_and_env_variable() {
+        let config = Config::default().for_revision("a");
+        assert_eq!(Some(&"a".to_owned()), config.constants.get("rev"));
+        assert_eq!(Some(&"a".to_owned()), config.env_variables.get("REV"));
+        assert_eq!(Some("a".to_owned()), config.active_revision);
+    }
+}
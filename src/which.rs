@@ -0,0 +1,102 @@
+//! Resolution of bare executable names (`"bash"`) to absolute paths.
+//!
+//! Used by [`Config::resolve_executable`] and [`Config::resolve_shell`] so
+//! that a missing tool or shell fails with a clear "not found" error up
+//! front, rather than as an opaque spawn failure once a test is already
+//! running.
+//!
+//! [`Config::resolve_executable`]: crate::config::Config::resolve_executable
+//! [`Config::resolve_shell`]: crate::config::Config::resolve_shell
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Searches `search_paths`, then the inherited `$PATH`, for an executable
+/// named `name`, returning the first match as an absolute path.
+///
+/// On Windows, if `name` has no extension, each directory is probed with
+/// every extension in `PATHEXT` (falling back to `.exe;.bat;.cmd` if
+/// `PATHEXT` isn't set); directories and non-executable files are skipped.
+pub fn resolve_executable<P: AsRef<Path>>(name: &str, search_paths: &[P]) -> Option<PathBuf> {
+    let candidates = executable_candidates(name);
+
+    let path_var = env::var_os("PATH").unwrap_or_default();
+    let inherited_paths: Vec<PathBuf> = env::split_paths(&path_var).collect();
+
+    search_paths.iter().map(|p| p.as_ref())
+        .chain(inherited_paths.iter().map(PathBuf::as_path))
+        .find_map(|dir| candidates.iter().map(|candidate| dir.join(candidate)).find(|p| is_executable_file(p)))
+        .map(|found| if found.is_absolute() {
+            found
+        } else {
+            env::current_dir().map(|cwd| cwd.join(&found)).unwrap_or(found)
+        })
+}
+
+#[cfg(windows)]
+fn executable_candidates(name: &str) -> Vec<String> {
+    if Path::new(name).extension().is_some() {
+        return vec![name.to_owned()];
+    }
+
+    let pathext = env::var("PATHEXT").unwrap_or_else(|_| ".exe;.bat;.cmd".to_owned());
+    pathext.split(';').filter(|ext| !ext.is_empty()).map(|ext| format!("{}{}", name, ext)).collect()
+}
+
+#[cfg(not(windows))]
+fn executable_candidates(name: &str) -> Vec<String> {
+    vec![name.to_owned()]
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match path.metadata() {
+        Ok(meta) => meta.is_file() && meta.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn finds_an_executable_in_a_search_path() {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().expect("failed to create a temp dir");
+        let tool_path = dir.path().join("lit-which-test-tool");
+        fs::write(&tool_path, "#!/bin/sh\necho hi\n").expect("failed to write fake tool");
+        fs::set_permissions(&tool_path, fs::Permissions::from_mode(0o755))
+            .expect("failed to mark fake tool executable");
+
+        let found = resolve_executable("lit-which-test-tool", &[dir.path()]);
+        assert_eq!(Some(tool_path), found);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn skips_a_non_executable_file() {
+        use std::fs;
+
+        let dir = tempfile::tempdir().expect("failed to create a temp dir");
+        fs::write(dir.path().join("lit-which-test-data"), "not a script").expect("failed to write data file");
+
+        let found = resolve_executable("lit-which-test-data", &[dir.path()]);
+        assert_eq!(None, found, "a non-executable file should not resolve");
+    }
+
+    #[test]
+    fn an_unknown_name_resolves_to_nothing() {
+        let found = resolve_executable("lit-which-test-tool-that-does-not-exist", &Vec::<PathBuf>::new());
+        assert_eq!(None, found);
+    }
+}